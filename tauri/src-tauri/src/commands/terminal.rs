@@ -1,4 +1,4 @@
-use crate::terminal::SessionManager;
+use crate::terminal::{OutputEncoding, SessionInspection, SessionManager, SessionTarget};
 use tauri::{AppHandle, State};
 
 /// Create a new terminal session
@@ -9,12 +9,43 @@ pub async fn create_terminal_session(
     working_directory: String,
     command: String,
     args: Vec<String>,
+    encoding: Option<OutputEncoding>,
+    scrollback_bytes: Option<usize>,
+    idle_timeout_secs: Option<u64>,
+    target: Option<SessionTarget>,
 ) -> Result<String, String> {
     manager
-        .create_session(app, &working_directory, &command, &args)
+        .create_session(
+            app,
+            &working_directory,
+            &command,
+            &args,
+            encoding,
+            scrollback_bytes,
+            idle_timeout_secs,
+            target,
+        )
         .await
 }
 
+/// Attach to a session, returning its buffered scrollback for replay.
+#[tauri::command]
+pub async fn terminal_attach(
+    manager: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<String, String> {
+    manager.attach(&session_id).await
+}
+
+/// Clear a session's buffered scrollback.
+#[tauri::command]
+pub async fn terminal_clear_scrollback(
+    manager: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<(), String> {
+    manager.clear_scrollback(&session_id).await
+}
+
 /// Write data to a terminal session
 #[tauri::command]
 pub async fn terminal_write(
@@ -36,6 +67,43 @@ pub async fn terminal_resize(
     manager.resize(&session_id, cols, rows).await
 }
 
+/// Report a session's descendant processes and the sockets they have open.
+#[tauri::command]
+pub async fn terminal_inspect(
+    manager: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<SessionInspection, String> {
+    manager.inspect(&session_id).await
+}
+
+/// Start recording a session's output as an asciicast v2 file.
+#[tauri::command]
+pub async fn terminal_start_recording(
+    manager: State<'_, SessionManager>,
+    session_id: String,
+    path: String,
+) -> Result<(), String> {
+    manager.start_recording(&session_id, &path).await
+}
+
+/// Stop recording a session, flushing and closing the recording file.
+#[tauri::command]
+pub async fn terminal_stop_recording(
+    manager: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<(), String> {
+    manager.stop_recording(&session_id).await
+}
+
+/// Kill a terminal session's child process without removing the session
+#[tauri::command]
+pub async fn terminal_kill(
+    manager: State<'_, SessionManager>,
+    session_id: String,
+) -> Result<(), String> {
+    manager.kill(&session_id).await
+}
+
 /// Close a terminal session
 #[tauri::command]
 pub async fn close_terminal_session(
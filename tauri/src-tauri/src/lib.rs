@@ -10,13 +10,21 @@ pub fn run() {
         .plugin(tauri_plugin_notification::init())
         .setup(|app| {
             // Initialize terminal session manager
-            app.manage(terminal::SessionManager::new());
+            let manager = terminal::SessionManager::new();
+            manager.spawn_idle_watcher(app.handle().clone());
+            app.manage(manager);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::terminal::create_terminal_session,
             commands::terminal::terminal_write,
             commands::terminal::terminal_resize,
+            commands::terminal::terminal_attach,
+            commands::terminal::terminal_clear_scrollback,
+            commands::terminal::terminal_inspect,
+            commands::terminal::terminal_start_recording,
+            commands::terminal::terminal_stop_recording,
+            commands::terminal::terminal_kill,
             commands::terminal::close_terminal_session,
             commands::notification::send_notification,
         ])
@@ -0,0 +1,140 @@
+//! Process-tree and socket introspection for a terminal session's child, used
+//! to show what a long-lived session is actually running and talking to.
+
+use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo};
+use serde::Serialize;
+use std::collections::HashSet;
+use sysinfo::{Pid, System};
+
+/// A single process in the session's descendant tree.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub cpu_usage: f32,
+    pub memory_bytes: u64,
+}
+
+/// A TCP or UDP socket owned by one of the session's descendant processes.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionInfo {
+    pub pid: u32,
+    pub protocol: String,
+    pub local_addr: String,
+    pub remote_addr: Option<String>,
+    pub state: String,
+}
+
+/// The result of inspecting a session's process tree and open sockets.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionInspection {
+    pub processes: Vec<ProcessInfo>,
+    pub connections: Vec<ConnectionInfo>,
+}
+
+/// Walk the process tree rooted at `root_pid` and list the TCP/UDP sockets
+/// any process in that tree has open.
+pub(crate) fn inspect(root_pid: u32) -> SessionInspection {
+    let mut sys = System::new_all();
+    sys.refresh_all();
+
+    let descendants = collect_descendants(&sys, root_pid);
+
+    let processes = descendants
+        .iter()
+        .filter_map(|pid| {
+            let process = sys.process(Pid::from_u32(*pid))?;
+            Some(ProcessInfo {
+                pid: *pid,
+                name: process.name().to_string_lossy().into_owned(),
+                cpu_usage: process.cpu_usage(),
+                memory_bytes: process.memory(),
+            })
+        })
+        .collect();
+
+    let connections = list_connections(&descendants);
+
+    SessionInspection {
+        processes,
+        connections,
+    }
+}
+
+/// Collect `root_pid` and every process transitively parented by it.
+fn collect_descendants(sys: &System, root_pid: u32) -> HashSet<u32> {
+    let mut descendants = HashSet::new();
+    descendants.insert(root_pid);
+
+    // Processes can be listed in any order, so repeatedly sweep until a pass
+    // finds no new descendants instead of assuming parents precede children.
+    loop {
+        let mut grew = false;
+        for (pid, process) in sys.processes() {
+            let pid = pid.as_u32();
+            if descendants.contains(&pid) {
+                continue;
+            }
+            if let Some(parent) = process.parent() {
+                if descendants.contains(&parent.as_u32()) {
+                    descendants.insert(pid);
+                    grew = true;
+                }
+            }
+        }
+        if !grew {
+            break;
+        }
+    }
+
+    descendants
+}
+
+fn list_connections(descendants: &HashSet<u32>) -> Vec<ConnectionInfo> {
+    let af_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
+    let proto_flags = ProtocolFlags::TCP | ProtocolFlags::UDP;
+
+    let Ok(sockets) = get_sockets_info(af_flags, proto_flags) else {
+        return Vec::new();
+    };
+
+    let mut connections = Vec::new();
+    for socket in sockets {
+        let owning_pids: Vec<u32> = socket
+            .associated_pids
+            .iter()
+            .copied()
+            .filter(|pid| descendants.contains(pid))
+            .collect();
+        if owning_pids.is_empty() {
+            continue;
+        }
+
+        let (protocol, local_addr, remote_addr, state) = match &socket.protocol_socket_info {
+            ProtocolSocketInfo::Tcp(tcp) => (
+                "tcp",
+                format!("{}:{}", tcp.local_addr, tcp.local_port),
+                Some(format!("{}:{}", tcp.remote_addr, tcp.remote_port)),
+                tcp.state.to_string(),
+            ),
+            ProtocolSocketInfo::Udp(udp) => (
+                "udp",
+                format!("{}:{}", udp.local_addr, udp.local_port),
+                None,
+                String::new(),
+            ),
+        };
+
+        for pid in owning_pids {
+            connections.push(ConnectionInfo {
+                pid,
+                protocol: protocol.to_string(),
+                local_addr: local_addr.clone(),
+                remote_addr: remote_addr.clone(),
+                state: state.clone(),
+            });
+        }
+    }
+
+    connections
+}
@@ -1,18 +1,75 @@
-//! Terminal session management using portable-pty for cross-platform PTY support.
+//! Terminal session management using portable-pty for cross-platform PTY support,
+//! or the `transport` module's SSH channels for remote sessions.
 
-use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
-use std::collections::HashMap;
+mod inspect;
+mod recording;
+mod transport;
+
+use base64::Engine;
+use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use recording::Recording;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::RwLock;
+use transport::{PtyChild, PtyResize, SshTransport};
+
+pub use inspect::SessionInspection;
+pub use transport::SessionTarget;
+
+/// Default cap for a session's scrollback buffer, in bytes.
+const DEFAULT_SCROLLBACK_BYTES: usize = 1024 * 1024;
+
+/// How often the idle-timeout watcher scans sessions for inactivity.
+const IDLE_SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Current time as milliseconds since the Unix epoch.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Controls how raw PTY bytes are turned into the payload delivered to the frontend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputEncoding {
+    /// Decode bytes as UTF-8, buffering incomplete multi-byte sequences across reads.
+    #[default]
+    Utf8,
+    /// Base64-encode each chunk verbatim, for binary-safe transport.
+    Raw,
+}
+
+/// The outcome of a terminated child process, reported to the frontend.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TerminalExitStatus {
+    pub code: Option<i32>,
+    /// The terminating signal's name (e.g. `"TERM"`), as reported by the
+    /// remote end of an SSH session. Local sessions never populate this:
+    /// `portable_pty::ExitStatus` doesn't expose a signal, only an exit code.
+    pub signal: Option<String>,
+}
 
 /// A single terminal session wrapping a PTY
 pub struct TerminalSession {
     pub id: String,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
-    master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
+    resizer: Arc<Mutex<Box<dyn PtyResize>>>,
+    child: Arc<Mutex<Box<dyn PtyChild>>>,
+    encoding: OutputEncoding,
+    scrollback: Arc<Mutex<VecDeque<u8>>>,
+    scrollback_cap: usize,
+    idle_timeout_secs: Option<u64>,
+    last_activity_ms: AtomicU64,
+    size: Mutex<(u16, u16)>,
+    recording: Arc<Mutex<Option<Recording>>>,
 }
 
 impl TerminalSession {
@@ -22,35 +79,236 @@ impl TerminalSession {
         writer
             .write_all(data)
             .map_err(|e| format!("Failed to write: {}", e))?;
-        writer.flush().map_err(|e| format!("Failed to flush: {}", e))
+        writer.flush().map_err(|e| format!("Failed to flush: {}", e))?;
+        self.touch_activity();
+        Ok(())
+    }
+
+    /// Record that the session was just used, resetting its idle clock.
+    fn touch_activity(&self) {
+        self.last_activity_ms.store(now_millis(), Ordering::Relaxed);
+    }
+
+    /// Whether the session has been inactive longer than its configured idle timeout.
+    fn is_idle(&self) -> bool {
+        match self.idle_timeout_secs {
+            Some(secs) => {
+                let last = self.last_activity_ms.load(Ordering::Relaxed);
+                now_millis().saturating_sub(last) >= secs * 1000
+            }
+            None => false,
+        }
     }
 
     /// Resize the terminal
     pub fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
-        let master = self.master.lock().map_err(|e| e.to_string())?;
-        master
-            .resize(PtySize {
-                rows,
-                cols,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .map_err(|e| format!("Failed to resize: {}", e))
+        let resizer = self.resizer.lock().map_err(|e| e.to_string())?;
+        resizer.resize(cols, rows)?;
+        if let Ok(mut size) = self.size.lock() {
+            *size = (cols, rows);
+        }
+        self.record_resize(cols, rows);
+        Ok(())
+    }
+
+    /// Start recording this session's output as an asciicast v2 file.
+    pub fn start_recording(&self, path: &str) -> Result<(), String> {
+        let (cols, rows) = *self.size.lock().map_err(|e| e.to_string())?;
+        let rec = Recording::start(path, cols, rows, now_millis() / 1000)?;
+        let mut recording = self.recording.lock().map_err(|e| e.to_string())?;
+        *recording = Some(rec);
+        Ok(())
+    }
+
+    /// Stop recording, flushing and closing the recording file.
+    pub fn stop_recording(&self) -> Result<(), String> {
+        let rec = {
+            let mut recording = self.recording.lock().map_err(|e| e.to_string())?;
+            recording.take()
+        };
+        match rec {
+            Some(rec) => rec.stop(),
+            None => Ok(()),
+        }
     }
+
+    /// Append an output event to the active recording, if any.
+    fn record_output(&self, chunk: &str) {
+        let Ok(mut recording) = self.recording.lock() else {
+            return;
+        };
+        if let Some(rec) = recording.as_mut() {
+            rec.record_output(chunk);
+        }
+    }
+
+    /// Append a resize event to the active recording, if any.
+    fn record_resize(&self, cols: u16, rows: u16) {
+        let Ok(mut recording) = self.recording.lock() else {
+            return;
+        };
+        if let Some(rec) = recording.as_mut() {
+            rec.record_resize(cols, rows);
+        }
+    }
+
+    /// Append raw output bytes to the scrollback buffer, trimming the oldest
+    /// bytes once the configured cap is exceeded.
+    fn push_scrollback(&self, data: &[u8]) {
+        let Ok(mut scrollback) = self.scrollback.lock() else {
+            return;
+        };
+        scrollback.extend(data.iter().copied());
+        let overflow = scrollback.len().saturating_sub(self.scrollback_cap);
+        if overflow > 0 {
+            scrollback.drain(..overflow);
+        }
+    }
+
+    /// Return the current scrollback contents, encoded per the session's output mode.
+    pub fn scrollback_snapshot(&self) -> Result<String, String> {
+        let scrollback = self.scrollback.lock().map_err(|e| e.to_string())?;
+        let bytes: Vec<u8> = scrollback.iter().copied().collect();
+        Ok(encode_chunk(&bytes, self.encoding))
+    }
+
+    /// Drop all buffered scrollback.
+    pub fn clear_scrollback(&self) -> Result<(), String> {
+        let mut scrollback = self.scrollback.lock().map_err(|e| e.to_string())?;
+        scrollback.clear();
+        Ok(())
+    }
+
+    /// Forcibly terminate the child process.
+    pub fn kill(&self) -> Result<(), String> {
+        let mut child = self.child.lock().map_err(|e| e.to_string())?;
+        child.kill()
+    }
+
+    /// Block until the child exits and report its exit status.
+    fn wait(&self) -> TerminalExitStatus {
+        let Ok(mut child) = self.child.lock() else {
+            return TerminalExitStatus::default();
+        };
+        child.wait()
+    }
+
+    /// The local OS pid of the session's process, if it has one to inspect.
+    pub fn pid(&self) -> Option<u32> {
+        let child = self.child.lock().ok()?;
+        child.pid()
+    }
+}
+
+/// Encode a whole byte slice per the session's output mode (used for one-shot
+/// transfers like scrollback replay, where there's no carry-over state to track).
+fn encode_chunk(bytes: &[u8], encoding: OutputEncoding) -> String {
+    match encoding {
+        OutputEncoding::Raw => base64::engine::general_purpose::STANDARD.encode(bytes),
+        OutputEncoding::Utf8 => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+/// Decode `chunk` as UTF-8, carrying any incomplete trailing bytes in `carry`
+/// across calls so a multibyte codepoint split across two reads round-trips
+/// intact instead of being replaced with `\u{fffd}`.
+fn decode_utf8_carry(carry: &mut Vec<u8>, chunk: &[u8]) -> String {
+    carry.extend_from_slice(chunk);
+    let mut output = String::new();
+    let mut start = 0;
+    loop {
+        match std::str::from_utf8(&carry[start..]) {
+            Ok(valid) => {
+                output.push_str(valid);
+                start = carry.len();
+                break;
+            }
+            Err(e) => {
+                let valid_up_to = start + e.valid_up_to();
+                output.push_str(
+                    // Safe: `valid_up_to` is exactly the boundary `from_utf8`
+                    // just validated up to.
+                    std::str::from_utf8(&carry[start..valid_up_to]).unwrap(),
+                );
+                match e.error_len() {
+                    // Truncated-but-possibly-valid codepoint at the very end:
+                    // hold it in `carry` for the next read instead of
+                    // replacing it with `\u{fffd}` early.
+                    None => {
+                        start = valid_up_to;
+                        break;
+                    }
+                    // Genuinely malformed bytes: lossy-decode just that run
+                    // and keep scanning the rest of the chunk for more valid
+                    // text, instead of stopping at the first bad byte.
+                    Some(bad_len) => {
+                        let bad_end = valid_up_to + bad_len;
+                        output.push_str(&String::from_utf8_lossy(&carry[valid_up_to..bad_end]));
+                        start = bad_end;
+                    }
+                }
+            }
+        }
+    }
+    carry.drain(..start);
+    output
+}
+
+/// Lossy-decode and drop any bytes still held in `carry`, for use when the
+/// stream is ending and there won't be a next read to complete them.
+fn flush_carry_lossy(carry: &mut Vec<u8>) -> Option<String> {
+    if carry.is_empty() {
+        return None;
+    }
+    let output = String::from_utf8_lossy(carry).to_string();
+    carry.clear();
+    Some(output)
 }
 
 /// Manages multiple terminal sessions
 pub struct SessionManager {
-    sessions: RwLock<HashMap<String, Arc<TerminalSession>>>,
+    sessions: Arc<RwLock<HashMap<String, Arc<TerminalSession>>>>,
 }
 
 impl SessionManager {
     pub fn new() -> Self {
         Self {
-            sessions: RwLock::new(HashMap::new()),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Spawn a background task that kills sessions which have been idle longer
+    /// than their configured `idle_timeout_secs`.
+    pub fn spawn_idle_watcher(&self, app: AppHandle) {
+        let sessions = self.sessions.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(IDLE_SCAN_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let idle_ids: Vec<String> = {
+                    let sessions = sessions.read().await;
+                    sessions
+                        .iter()
+                        .filter(|(_, session)| session.is_idle())
+                        .map(|(id, _)| id.clone())
+                        .collect()
+                };
+
+                for id in idle_ids {
+                    let session = {
+                        let mut sessions = sessions.write().await;
+                        sessions.remove(&id)
+                    };
+                    if let Some(session) = session {
+                        let _ = session.kill();
+                        let _ = app.emit(&format!("terminal-idle-timeout-{}", id), ());
+                    }
+                }
+            }
+        });
+    }
+
     /// Create a new terminal session and start streaming output
     pub async fn create_session(
         &self,
@@ -58,63 +316,118 @@ impl SessionManager {
         working_directory: &str,
         command: &str,
         args: &[String],
+        encoding: Option<OutputEncoding>,
+        scrollback_bytes: Option<usize>,
+        idle_timeout_secs: Option<u64>,
+        target: Option<SessionTarget>,
     ) -> Result<String, String> {
-        let pty_system = native_pty_system();
-
-        let pair = pty_system
-            .openpty(PtySize {
-                rows: 24,
-                cols: 80,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .map_err(|e| format!("Failed to open PTY: {}", e))?;
-
-        let mut cmd = CommandBuilder::new(command);
-        for arg in args {
-            cmd.arg(arg);
-        }
-        cmd.cwd(working_directory);
+        let (writer, reader, resizer, child): (
+            Box<dyn Write + Send>,
+            Box<dyn Read + Send>,
+            Box<dyn PtyResize>,
+            Box<dyn PtyChild>,
+        ) = match target.unwrap_or_default() {
+            SessionTarget::Local => {
+                let pty_system = native_pty_system();
+
+                let pair = pty_system
+                    .openpty(PtySize {
+                        rows: 24,
+                        cols: 80,
+                        pixel_width: 0,
+                        pixel_height: 0,
+                    })
+                    .map_err(|e| format!("Failed to open PTY: {}", e))?;
 
-        // Set terminal environment variables
-        cmd.env("TERM", "xterm-256color");
-        cmd.env("COLORTERM", "truecolor");
-        cmd.env("LANG", "en_US.UTF-8");
+                let mut cmd = CommandBuilder::new(command);
+                for arg in args {
+                    cmd.arg(arg);
+                }
+                cmd.cwd(working_directory);
 
-        let _child = pair
-            .slave
-            .spawn_command(cmd)
-            .map_err(|e| format!("Failed to spawn command: {}", e))?;
+                // Set terminal environment variables
+                cmd.env("TERM", "xterm-256color");
+                cmd.env("COLORTERM", "truecolor");
+                cmd.env("LANG", "en_US.UTF-8");
 
-        let writer = pair
-            .master
-            .take_writer()
-            .map_err(|e| format!("Failed to get writer: {}", e))?;
+                let child = pair
+                    .slave
+                    .spawn_command(cmd)
+                    .map_err(|e| format!("Failed to spawn command: {}", e))?;
 
-        let reader = pair
-            .master
-            .try_clone_reader()
-            .map_err(|e| format!("Failed to get reader: {}", e))?;
+                let writer = pair
+                    .master
+                    .take_writer()
+                    .map_err(|e| format!("Failed to get writer: {}", e))?;
+
+                let reader = pair
+                    .master
+                    .try_clone_reader()
+                    .map_err(|e| format!("Failed to get reader: {}", e))?;
+
+                (
+                    writer,
+                    reader,
+                    Box::new(pair.master) as Box<dyn PtyResize>,
+                    Box::new(child) as Box<dyn PtyChild>,
+                )
+            }
+            SessionTarget::Ssh {
+                host,
+                user,
+                port,
+                identity,
+            } => {
+                let ssh = SshTransport::connect(
+                    &host,
+                    &user,
+                    port,
+                    identity.as_deref(),
+                    80,
+                    24,
+                    command,
+                    args,
+                )?;
+                (
+                    ssh.writer(),
+                    ssh.reader(),
+                    Box::new(ssh.clone()) as Box<dyn PtyResize>,
+                    Box::new(ssh) as Box<dyn PtyChild>,
+                )
+            }
+        };
 
         let id = uuid::Uuid::new_v4().to_string();
+        let encoding = encoding.unwrap_or_default();
+        let scrollback_cap = scrollback_bytes.unwrap_or(DEFAULT_SCROLLBACK_BYTES);
 
         let session = Arc::new(TerminalSession {
             id: id.clone(),
             writer: Arc::new(Mutex::new(writer)),
-            master: Arc::new(Mutex::new(pair.master)),
+            resizer: Arc::new(Mutex::new(resizer)),
+            child: Arc::new(Mutex::new(child)),
+            encoding,
+            scrollback: Arc::new(Mutex::new(VecDeque::with_capacity(
+                scrollback_cap.min(DEFAULT_SCROLLBACK_BYTES),
+            ))),
+            scrollback_cap,
+            idle_timeout_secs,
+            last_activity_ms: AtomicU64::new(now_millis()),
+            size: Mutex::new((80, 24)),
+            recording: Arc::new(Mutex::new(None)),
         });
 
         // Store session
         {
             let mut sessions = self.sessions.write().await;
-            sessions.insert(id.clone(), session);
+            sessions.insert(id.clone(), session.clone());
         }
 
         // Start output streaming thread
         let session_id = id.clone();
         let app_handle = app.clone();
         thread::spawn(move || {
-            Self::stream_output(reader, session_id, app_handle);
+            Self::stream_output(reader, session_id, app_handle, encoding, session);
         });
 
         Ok(id)
@@ -125,31 +438,68 @@ impl SessionManager {
         mut reader: Box<dyn Read + Send>,
         session_id: String,
         app: AppHandle,
+        encoding: OutputEncoding,
+        session: Arc<TerminalSession>,
     ) {
         let mut buffer = [0u8; 4096];
+        let mut carry: Vec<u8> = Vec::new();
         let event_name = format!("terminal-output-{}", session_id);
 
         loop {
             match reader.read(&mut buffer) {
                 Ok(0) => {
                     // EOF - process terminated
-                    let _ = app.emit(&format!("terminal-closed-{}", session_id), ());
+                    Self::flush_trailing_carry(&mut carry, &session, &app, &event_name, encoding);
+                    let status = session.wait();
+                    let _ = app.emit(&format!("terminal-closed-{}", session_id), status);
                     break;
                 }
                 Ok(n) => {
-                    // Convert to string (lossy for invalid UTF-8)
-                    let output = String::from_utf8_lossy(&buffer[..n]).to_string();
+                    session.touch_activity();
+                    session.push_scrollback(&buffer[..n]);
+                    // Carry-decode once per read so both the recording and the
+                    // Utf8 frontend path see the same properly reassembled text
+                    // instead of each re-deriving it (or naively re-decoding
+                    // `buffer[..n]` in isolation, which mangles codepoints split
+                    // across two reads).
+                    let decoded = decode_utf8_carry(&mut carry, &buffer[..n]);
+                    session.record_output(&decoded);
+                    let output = match encoding {
+                        OutputEncoding::Raw => encode_chunk(&buffer[..n], OutputEncoding::Raw),
+                        OutputEncoding::Utf8 => decoded,
+                    };
                     let _ = app.emit(&event_name, output);
                 }
                 Err(e) => {
                     eprintln!("Error reading PTY: {}", e);
-                    let _ = app.emit(&format!("terminal-closed-{}", session_id), ());
+                    Self::flush_trailing_carry(&mut carry, &session, &app, &event_name, encoding);
+                    let status = session.wait();
+                    let _ = app.emit(&format!("terminal-closed-{}", session_id), status);
                     break;
                 }
             }
         }
     }
 
+    /// Flush any bytes still held in `carry` when the stream is ending, so a
+    /// multibyte codepoint split across the last two reads isn't silently
+    /// dropped from the recording or the live `Utf8` output.
+    fn flush_trailing_carry(
+        carry: &mut Vec<u8>,
+        session: &Arc<TerminalSession>,
+        app: &AppHandle,
+        event_name: &str,
+        encoding: OutputEncoding,
+    ) {
+        let Some(trailing) = flush_carry_lossy(carry) else {
+            return;
+        };
+        session.record_output(&trailing);
+        if encoding == OutputEncoding::Utf8 {
+            let _ = app.emit(event_name, trailing);
+        }
+    }
+
     /// Write to a terminal session
     pub async fn write(&self, session_id: &str, data: &[u8]) -> Result<(), String> {
         let sessions = self.sessions.read().await;
@@ -168,12 +518,78 @@ impl SessionManager {
         session.resize(cols, rows)
     }
 
+    /// Attach to a session, returning its buffered scrollback so a freshly
+    /// mounted frontend view can repaint immediately.
+    pub async fn attach(&self, session_id: &str) -> Result<String, String> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        session.scrollback_snapshot()
+    }
+
+    /// Clear a session's buffered scrollback.
+    pub async fn clear_scrollback(&self, session_id: &str) -> Result<(), String> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        session.clear_scrollback()
+    }
+
+    /// Report the session's descendant processes and the sockets they have open.
+    pub async fn inspect(&self, session_id: &str) -> Result<SessionInspection, String> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        let pid = session
+            .pid()
+            .ok_or_else(|| "Session has no local process to inspect".to_string())?;
+        Ok(inspect::inspect(pid))
+    }
+
+    /// Start recording a session's output as an asciicast v2 file.
+    pub async fn start_recording(&self, session_id: &str, path: &str) -> Result<(), String> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        session.start_recording(path)
+    }
+
+    /// Stop recording a session, flushing and closing the recording file.
+    pub async fn stop_recording(&self, session_id: &str) -> Result<(), String> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        session.stop_recording()
+    }
+
+    /// Kill a session's child process without removing the session from the map.
+    pub async fn kill(&self, session_id: &str) -> Result<(), String> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        session.kill()
+    }
+
     /// Close a terminal session
     pub async fn close(&self, session_id: &str) -> Result<(), String> {
-        let mut sessions = self.sessions.write().await;
-        sessions
-            .remove(session_id)
-            .ok_or_else(|| "Session not found".to_string())?;
+        let session = {
+            let mut sessions = self.sessions.write().await;
+            sessions
+                .remove(session_id)
+                .ok_or_else(|| "Session not found".to_string())?
+        };
+        // Dropping `session` alone wouldn't touch the child: nothing in
+        // `TerminalSession`/`Box<dyn PtyChild>` kills it on `Drop`, and the
+        // read thread holds its own `Arc<TerminalSession>` keeping it alive
+        // regardless. Kill it explicitly instead. Ignore errors, since the
+        // process may have already exited on its own.
+        let _ = session.kill();
         Ok(())
     }
 }
@@ -183,3 +599,65 @@ impl Default for SessionManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_utf8_carry_passes_through_complete_ascii() {
+        let mut carry = Vec::new();
+        assert_eq!(decode_utf8_carry(&mut carry, b"hello"), "hello");
+        assert!(carry.is_empty());
+    }
+
+    #[test]
+    fn decode_utf8_carry_holds_a_split_codepoint_for_the_next_read() {
+        let mut carry = Vec::new();
+        let euro = "€".as_bytes(); // 3 bytes: 0xE2 0x82 0xAC
+        assert_eq!(decode_utf8_carry(&mut carry, &euro[..1]), "");
+        assert_eq!(carry, euro[..1]);
+        assert_eq!(decode_utf8_carry(&mut carry, &euro[1..]), "€");
+        assert!(carry.is_empty());
+    }
+
+    #[test]
+    fn decode_utf8_carry_repairs_a_single_bad_byte_and_keeps_trailing_text() {
+        let mut carry = Vec::new();
+        let output = decode_utf8_carry(&mut carry, b"A\xFFB");
+        assert_eq!(output, "A\u{FFFD}B");
+        assert!(carry.is_empty());
+    }
+
+    #[test]
+    fn decode_utf8_carry_repairs_multiple_bad_runs_in_one_chunk() {
+        let mut carry = Vec::new();
+        let output = decode_utf8_carry(&mut carry, b"A\xFFB\xFEC");
+        assert_eq!(output, "A\u{FFFD}B\u{FFFD}C");
+        assert!(carry.is_empty());
+    }
+
+    #[test]
+    fn decode_utf8_carry_keeps_trailing_incomplete_codepoint_after_bad_byte() {
+        let mut carry = Vec::new();
+        let euro = "€".as_bytes();
+        let mut chunk = b"A\xFF".to_vec();
+        chunk.extend_from_slice(&euro[..2]);
+        let output = decode_utf8_carry(&mut carry, &chunk);
+        assert_eq!(output, "A\u{FFFD}");
+        assert_eq!(carry, euro[..2]);
+    }
+
+    #[test]
+    fn flush_carry_lossy_returns_none_when_empty() {
+        let mut carry = Vec::new();
+        assert_eq!(flush_carry_lossy(&mut carry), None);
+    }
+
+    #[test]
+    fn flush_carry_lossy_decodes_and_clears_remaining_bytes() {
+        let mut carry = "€".as_bytes()[..2].to_vec();
+        assert_eq!(flush_carry_lossy(&mut carry), Some("\u{FFFD}".to_string()));
+        assert!(carry.is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+//! asciicast v2 session recording, so a terminal session can be replayed later
+//! with any standard asciinema-compatible player.
+
+use serde_json::json;
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+
+/// An active recording: an open asciicast v2 file plus the instant it started,
+/// used to compute each event's `elapsed_seconds`.
+pub(crate) struct Recording {
+    file: File,
+    started_at: Instant,
+}
+
+impl Recording {
+    /// Start a new recording, writing the asciicast v2 header line.
+    pub(crate) fn start(
+        path: &str,
+        cols: u16,
+        rows: u16,
+        timestamp_secs: u64,
+    ) -> Result<Self, String> {
+        let mut file =
+            File::create(path).map_err(|e| format!("Failed to create recording file: {}", e))?;
+        let header = json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": timestamp_secs,
+        });
+        writeln!(file, "{}", header)
+            .map_err(|e| format!("Failed to write recording header: {}", e))?;
+        Ok(Self {
+            file,
+            started_at: Instant::now(),
+        })
+    }
+
+    /// Append an output event with the chunk's decoded text.
+    pub(crate) fn record_output(&mut self, chunk: &str) {
+        let event = json!([self.elapsed(), "o", chunk]);
+        let _ = writeln!(self.file, "{}", event);
+    }
+
+    /// Append a resize event.
+    pub(crate) fn record_resize(&mut self, cols: u16, rows: u16) {
+        let event = json!([self.elapsed(), "r", format!("{}x{}", cols, rows)]);
+        let _ = writeln!(self.file, "{}", event);
+    }
+
+    /// Flush and close the recording file.
+    pub(crate) fn stop(mut self) -> Result<(), String> {
+        self.file
+            .flush()
+            .map_err(|e| format!("Failed to flush recording: {}", e))
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.started_at.elapsed().as_secs_f64()
+    }
+}
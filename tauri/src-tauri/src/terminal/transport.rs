@@ -0,0 +1,324 @@
+//! Transport abstraction so a `TerminalSession` can run its command either in
+//! a local PTY or in a PTY channel opened over SSH, behind the same
+//! resize/kill/wait surface the session already relies on.
+
+use super::TerminalExitStatus;
+use portable_pty::{Child, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
+use ssh2::{Error as SshError, ErrorCode, Session as SshSession};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// libssh2's EAGAIN, returned by any channel operation that would otherwise
+/// block once the session has been switched to non-blocking mode.
+const LIBSSH2_ERROR_EAGAIN: i32 = -37;
+
+/// How long to sleep between retries of a channel operation that returned
+/// EAGAIN, so the mutex is released while idle instead of spin-locking.
+const EAGAIN_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+fn is_would_block(err: &SshError) -> bool {
+    matches!(err.code(), ErrorCode::Session(LIBSSH2_ERROR_EAGAIN))
+}
+
+/// Where a terminal session's command actually runs.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SessionTarget {
+    /// Spawn the command in a local PTY via `native_pty_system`.
+    Local,
+    /// Open a PTY channel over SSH and run the command there.
+    Ssh {
+        host: String,
+        user: String,
+        port: Option<u16>,
+        identity: Option<String>,
+    },
+}
+
+impl Default for SessionTarget {
+    fn default() -> Self {
+        SessionTarget::Local
+    }
+}
+
+/// Resizes a session's PTY, whichever transport backs it.
+pub(crate) trait PtyResize: Send {
+    fn resize(&self, cols: u16, rows: u16) -> Result<(), String>;
+}
+
+/// Controls a session's process, whichever transport backs it.
+pub(crate) trait PtyChild: Send {
+    fn kill(&mut self) -> Result<(), String>;
+    fn wait(&mut self) -> TerminalExitStatus;
+    /// The local OS pid of the process, if one exists to inspect (an SSH
+    /// session's command runs on the remote host, so it has none).
+    fn pid(&self) -> Option<u32>;
+}
+
+impl PtyResize for Box<dyn MasterPty + Send> {
+    fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
+        self.as_ref()
+            .resize(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| format!("Failed to resize: {}", e))
+    }
+}
+
+impl PtyChild for Box<dyn Child + Send + Sync> {
+    fn kill(&mut self) -> Result<(), String> {
+        Child::kill(self.as_mut()).map_err(|e| format!("Failed to kill child: {}", e))
+    }
+
+    fn wait(&mut self) -> TerminalExitStatus {
+        match Child::wait(self.as_mut()) {
+            Ok(status) => TerminalExitStatus {
+                code: Some(status.exit_code() as i32),
+                signal: None,
+            },
+            Err(_) => TerminalExitStatus::default(),
+        }
+    }
+
+    fn pid(&self) -> Option<u32> {
+        Child::process_id(self.as_ref())
+    }
+}
+
+/// Shared state for an SSH-backed session. The session is kept alongside the
+/// channel since the channel is only valid as long as its session's socket is.
+struct SshHandle {
+    _session: SshSession,
+    channel: ssh2::Channel,
+}
+
+/// A PTY channel opened over SSH, shared between the reader/writer halves
+/// handed to `stream_output`/`write` and the resize/kill handles kept on the
+/// `TerminalSession`.
+#[derive(Clone)]
+pub(crate) struct SshTransport(Arc<Mutex<SshHandle>>);
+
+impl SshTransport {
+    /// Connect, authenticate, open a PTY channel, and start the given command
+    /// (or a login shell if `command` is empty).
+    pub(crate) fn connect(
+        host: &str,
+        user: &str,
+        port: Option<u16>,
+        identity: Option<&str>,
+        cols: u16,
+        rows: u16,
+        command: &str,
+        args: &[String],
+    ) -> Result<Self, String> {
+        let port = port.unwrap_or(22);
+        let tcp = TcpStream::connect((host, port))
+            .map_err(|e| format!("Failed to connect to {}:{}: {}", host, port, e))?;
+
+        let mut session =
+            SshSession::new().map_err(|e| format!("Failed to start SSH session: {}", e))?;
+        session.set_tcp_stream(tcp);
+        session
+            .handshake()
+            .map_err(|e| format!("SSH handshake failed: {}", e))?;
+
+        match identity {
+            Some(key_path) => session
+                .userauth_pubkey_file(user, None, Path::new(key_path), None)
+                .map_err(|e| format!("SSH key auth failed: {}", e))?,
+            None => session
+                .userauth_agent(user)
+                .map_err(|e| format!("SSH agent auth failed: {}", e))?,
+        }
+
+        let mut channel = session
+            .channel_session()
+            .map_err(|e| format!("Failed to open SSH channel: {}", e))?;
+        channel
+            .request_pty(
+                "xterm-256color",
+                None,
+                Some((cols as u32, rows as u32, 0, 0)),
+            )
+            .map_err(|e| format!("Failed to request remote PTY: {}", e))?;
+
+        if command.is_empty() {
+            channel
+                .shell()
+                .map_err(|e| format!("Failed to start remote shell: {}", e))?;
+        } else {
+            channel
+                .exec(&build_remote_command(command, args))
+                .map_err(|e| format!("Failed to exec remote command: {}", e))?;
+        }
+
+        // Setup above needs blocking calls, but from here on the channel is
+        // read/written from a dedicated thread while resize/kill/the idle
+        // watcher reach in from others. Non-blocking mode means a read with
+        // nothing to return yields EAGAIN instead of parking the thread (and
+        // the mutex along with it), so those calls don't have to wait for the
+        // next byte of remote output.
+        session.set_blocking(false);
+
+        Ok(SshTransport(Arc::new(Mutex::new(SshHandle {
+            _session: session,
+            channel,
+        }))))
+    }
+
+    pub(crate) fn reader(&self) -> Box<dyn Read + Send> {
+        Box::new(SshReader(self.0.clone()))
+    }
+
+    pub(crate) fn writer(&self) -> Box<dyn Write + Send> {
+        Box::new(SshWriter(self.0.clone()))
+    }
+}
+
+impl PtyResize for SshTransport {
+    fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
+        loop {
+            let handle = self.0.lock().map_err(|e| e.to_string())?;
+            match handle
+                .channel
+                .request_pty_size(cols as u32, rows as u32, None, None)
+            {
+                Ok(()) => return Ok(()),
+                Err(e) if is_would_block(&e) => {
+                    drop(handle);
+                    thread::sleep(EAGAIN_RETRY_DELAY);
+                }
+                Err(e) => return Err(format!("Failed to resize remote PTY: {}", e)),
+            }
+        }
+    }
+}
+
+impl PtyChild for SshTransport {
+    fn kill(&mut self) -> Result<(), String> {
+        loop {
+            let mut handle = self.0.lock().map_err(|e| e.to_string())?;
+            match handle.channel.close() {
+                Ok(()) => return Ok(()),
+                Err(e) if is_would_block(&e) => {
+                    drop(handle);
+                    thread::sleep(EAGAIN_RETRY_DELAY);
+                }
+                Err(e) => return Err(format!("Failed to close SSH channel: {}", e)),
+            }
+        }
+    }
+
+    fn wait(&mut self) -> TerminalExitStatus {
+        loop {
+            let Ok(mut handle) = self.0.lock() else {
+                return TerminalExitStatus::default();
+            };
+            match handle.channel.wait_close() {
+                Ok(()) => {
+                    return TerminalExitStatus {
+                        code: handle.channel.exit_status().ok(),
+                        signal: handle
+                            .channel
+                            .exit_signal()
+                            .ok()
+                            .and_then(|s| s.exit_signal),
+                    };
+                }
+                Err(e) if is_would_block(&e) => {
+                    drop(handle);
+                    thread::sleep(EAGAIN_RETRY_DELAY);
+                }
+                Err(_) => return TerminalExitStatus::default(),
+            }
+        }
+    }
+
+    fn pid(&self) -> Option<u32> {
+        // The command runs on the remote host, so there's no local pid to report.
+        None
+    }
+}
+
+struct SshReader(Arc<Mutex<SshHandle>>);
+
+impl Read for SshReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let mut handle = self.0.lock().map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::Other, "SSH channel lock poisoned")
+            })?;
+            match handle.channel.read(buf) {
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    drop(handle);
+                    thread::sleep(EAGAIN_RETRY_DELAY);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+struct SshWriter(Arc<Mutex<SshHandle>>);
+
+impl Write for SshWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        loop {
+            let mut handle = self.0.lock().map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::Other, "SSH channel lock poisoned")
+            })?;
+            match handle.channel.write(buf) {
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    drop(handle);
+                    thread::sleep(EAGAIN_RETRY_DELAY);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        loop {
+            let mut handle = self.0.lock().map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::Other, "SSH channel lock poisoned")
+            })?;
+            match handle.channel.flush() {
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    drop(handle);
+                    thread::sleep(EAGAIN_RETRY_DELAY);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Build a single exec command line, single-quoting the command and args that need it.
+fn build_remote_command(command: &str, args: &[String]) -> String {
+    let mut cmdline = shell_quote(command);
+    for arg in args {
+        cmdline.push(' ');
+        cmdline.push_str(&shell_quote(arg));
+    }
+    cmdline
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}